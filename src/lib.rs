@@ -18,9 +18,9 @@ extern crate cast;
 extern crate embedded_hal as hal;
 extern crate generic_array;
 
-use core::mem;
+use core::{cmp, mem};
 
-use cast::u16;
+use cast::{f32, i32, u16, usize};
 use generic_array::typenum::consts::*;
 use generic_array::{ArrayLength, GenericArray};
 use hal::blocking::i2c::{Write, WriteRead};
@@ -31,30 +31,73 @@ mod mag;
 /// LSM303DLHC driver
 pub struct Lsm303dlhc<I2C> {
     i2c: I2C,
+    sensitivity: Sensitivity,
+    mag_gain: MagGain,
 }
 
 impl<I2C, E> Lsm303dlhc<I2C>
 where
     I2C: WriteRead<Error = E> + Write<Error = E>,
 {
-    /// Creates a new driver from a I2C peripheral
+    /// Creates a new driver from a I2C peripheral, using the default `Config`
     pub fn new(i2c: I2C) -> Result<Self, E> {
-        let mut lsm303dlhc = Lsm303dlhc { i2c };
+        Self::new_with_config(i2c, Config::default())
+    }
 
-        // TODO reset all the registers / the device
+    /// Creates a new driver from a I2C peripheral, fully initialized according to `config`
+    pub fn new_with_config(i2c: I2C, config: Config) -> Result<Self, E> {
+        let mut lsm303dlhc = Lsm303dlhc {
+            i2c,
+            sensitivity: config.accel_sensitivity,
+            mag_gain: config.mag_gain,
+        };
 
-        // configure the accelerometer to operate at 400 Hz
-        lsm303dlhc.write_accel_register(accel::Register::CTRL_REG1_A, 0b0111_0_111)?;
+        lsm303dlhc.reset()?;
 
-        // configure the magnetometer to operate in continuous mode
-        lsm303dlhc.write_mag_register(mag::Register::MR_REG_M, 0b00)?;
+        // configure the accelerometer output data rate, sensitivity and axis enables
+        lsm303dlhc.write_accel_register(
+            accel::Register::CTRL_REG1_A,
+            ((config.accel_odr as u8) << 4) | 0b0111,
+        )?;
+        lsm303dlhc.write_accel_register(
+            accel::Register::CTRL_REG4_A,
+            ((config.block_data_update as u8) << 7)
+                | (config.accel_sensitivity.value() << 4)
+                | ((config.high_resolution as u8) << 3),
+        )?;
 
-        // enable the temperature sensor
-        lsm303dlhc.write_mag_register(mag::Register::CRA_REG_M, 0b0001000 | (1 << 7))?;
+        // configure the magnetometer to operate in continuous-conversion mode
+        lsm303dlhc.write_mag_register(mag::Register::MR_REG_M, 0b00)?;
+        lsm303dlhc.write_mag_register(
+            mag::Register::CRA_REG_M,
+            ((config.temp_enable as u8) << 7) | ((config.mag_odr as u8) << 2),
+        )?;
+        lsm303dlhc.write_mag_register(mag::Register::CRB_REG_M, config.mag_gain.value() << 5)?;
 
         Ok(lsm303dlhc)
     }
 
+    /// Reads the magnetometer identification registers (`IRA_REG_M`, `IRB_REG_M`, `IRC_REG_M`)
+    ///
+    /// On a genuine LSM303DLHC these read back as `[0x48, 0x34, 0x33]` (ASCII `"H43"`), which
+    /// callers can check against the bus contents before trusting the rest of the device
+    pub fn who_am_i(&mut self) -> Result<[u8; 3], E> {
+        Ok([
+            self.read_mag_register(mag::Register::IRA_REG_M)?,
+            self.read_mag_register(mag::Register::IRB_REG_M)?,
+            self.read_mag_register(mag::Register::IRC_REG_M)?,
+        ])
+    }
+
+    // Reboots the accelerometer's memory content and waits for it to complete
+    fn reset(&mut self) -> Result<(), E> {
+        self.write_accel_register(accel::Register::CTRL_REG5_A, 1 << 7)?;
+
+        while self.read_accel_register(accel::Register::CTRL_REG5_A)? & (1 << 7) != 0 {}
+
+        Ok(())
+    }
+
     /// Accelerometer measurements
     pub fn accel(&mut self) -> Result<I16x3, E> {
         let buffer: GenericArray<u8, U6> = self.read_accel_registers(accel::Register::OUT_X_L_A)?;
@@ -66,6 +109,35 @@ where
         })
     }
 
+    /// Accelerometer measurements, scaled to milli-g (mg) using the
+    /// currently configured `Sensitivity`
+    pub fn acceleration(&mut self) -> Result<I32x3, E> {
+        let raw = self.accel()?;
+        let mg_per_lsb = i32(self.sensitivity.mg_per_lsb());
+
+        Ok(I32x3 {
+            x: i32(raw.x >> 4) * mg_per_lsb,
+            y: i32(raw.y >> 4) * mg_per_lsb,
+            z: i32(raw.z >> 4) * mg_per_lsb,
+        })
+    }
+
+    /// Reads the accelerometer status
+    pub fn accel_status(&mut self) -> Result<AccelStatus, E> {
+        let r = self.read_accel_register(accel::Register::STATUS_REG_A)?;
+
+        Ok(AccelStatus {
+            x_new_data: r & (1 << 0) != 0,
+            y_new_data: r & (1 << 1) != 0,
+            z_new_data: r & (1 << 2) != 0,
+            zyx_new_data: r & (1 << 3) != 0,
+            x_overrun: r & (1 << 4) != 0,
+            y_overrun: r & (1 << 5) != 0,
+            z_overrun: r & (1 << 6) != 0,
+            zyx_overrun: r & (1 << 7) != 0,
+        })
+    }
+
     /// Sets the accelerometer output data rate
     pub fn accel_odr(&mut self, odr: AccelOdr) -> Result<(), E> {
         self.modify_accel_register(accel::Register::CTRL_REG1_A, |r| {
@@ -84,6 +156,45 @@ where
         })
     }
 
+    /// Magnetometer measurements, scaled to milligauss (mG) using the
+    /// currently configured gain
+    ///
+    /// The XY and Z axes have different LSB-per-gauss factors
+    pub fn magnetic_field(&mut self) -> Result<I32x3, E> {
+        let raw = self.mag()?;
+
+        Ok(self.scale_milligauss(i32(raw.x), i32(raw.y), i32(raw.z)))
+    }
+
+    /// Magnetometer measurements, scaled to milligauss (mG) and hard/soft-iron corrected using
+    /// `calibration` (see `MagCalibration`)
+    pub fn magnetic_field_calibrated(&mut self, calibration: &MagCalibration) -> Result<I32x3, E> {
+        let raw = self.mag()?;
+        let (x, y, z) = calibration.apply(&raw);
+
+        Ok(self.scale_milligauss(x, y, z))
+    }
+
+    fn scale_milligauss(&self, x: i32, y: i32, z: i32) -> I32x3 {
+        let (xy, z_gain) = (i32(self.mag_gain.xy()), i32(self.mag_gain.z()));
+
+        I32x3 {
+            x: x * 1000 / xy,
+            y: y * 1000 / xy,
+            z: z * 1000 / z_gain,
+        }
+    }
+
+    /// Reads the magnetometer status
+    pub fn mag_status(&mut self) -> Result<MagStatus, E> {
+        let r = self.read_mag_register(mag::Register::SR_REG_M)?;
+
+        Ok(MagStatus {
+            data_ready: r & (1 << 0) != 0,
+            lock: r & (1 << 1) != 0,
+        })
+    }
+
     /// Sets the magnetometer output data rate
     pub fn mag_odr(&mut self, odr: MagOdr) -> Result<(), E> {
         self.modify_mag_register(mag::Register::CRA_REG_M, |r| {
@@ -91,6 +202,15 @@ where
         })
     }
 
+    /// Changes the `gain` (and therefore the full-scale range) of the magnetometer
+    pub fn set_mag_gain(&mut self, gain: MagGain) -> Result<(), E> {
+        self.modify_mag_register(mag::Register::CRB_REG_M, |r| {
+            r & !(0b111 << 5) | (gain.value() << 5)
+        })?;
+        self.mag_gain = gain;
+        Ok(())
+    }
+
     /// Temperature sensor measurement
     ///
     /// - Resolution: 12-bit
@@ -102,13 +222,162 @@ where
         Ok(((u16(temp_out_l) + (u16(temp_out_h) << 8)) as i16) >> 4)
     }
 
+    /// Temperature sensor measurement, converted to degrees Celsius
+    ///
+    /// - Resolution: 8 LSB / °C
+    pub fn temperature(&mut self) -> Result<f32, E> {
+        let raw = self.temp()?;
+
+        Ok(25.0 + f32(raw) / 8.0)
+    }
+
     /// Changes the `sensitivity` of the accelerometer
     pub fn set_accel_sensitivity(&mut self, sensitivity: Sensitivity) -> Result<(), E> {
         self.modify_accel_register(accel::Register::CTRL_REG4_A, |r| {
             r & !(0b11 << 4) | (sensitivity.value() << 4)
+        })?;
+        self.sensitivity = sensitivity;
+        Ok(())
+    }
+
+    /// Routes (or unroutes) an interrupt `source` to the INT1 pin, via `CTRL_REG3_A`
+    pub fn enable_int1(&mut self, source: Int1Source, enable: bool) -> Result<(), E> {
+        self.modify_accel_register(accel::Register::CTRL_REG3_A, |r| {
+            if enable {
+                r | source.mask()
+            } else {
+                r & !source.mask()
+            }
         })
     }
 
+    /// Routes (or unroutes) an interrupt `source` to the INT2 pin, via `CTRL_REG6_A`
+    pub fn enable_int2(&mut self, source: Int2Source, enable: bool) -> Result<(), E> {
+        self.modify_accel_register(accel::Register::CTRL_REG6_A, |r| {
+            if enable {
+                r | source.mask()
+            } else {
+                r & !source.mask()
+            }
+        })
+    }
+
+    /// Sets the AOI threshold (`INT1_THS_A` / `INT2_THS_A`) of the given interrupt pin
+    ///
+    /// The threshold is a 7-bit value; 1 LSB corresponds to 16 mg at the `G1` sensitivity
+    pub fn set_interrupt_threshold(&mut self, pin: InterruptPin, ths: u8) -> Result<(), E> {
+        self.write_accel_register(pin.ths_register(), ths & 0b0111_1111)
+    }
+
+    /// Sets the AOI duration (`INT1_DURATION_A` / `INT2_DURATION_A`) of the given interrupt pin
+    ///
+    /// The duration is a 7-bit value in units of `1 / ODR`
+    pub fn set_interrupt_duration(&mut self, pin: InterruptPin, duration: u8) -> Result<(), E> {
+        self.write_accel_register(pin.duration_register(), duration & 0b0111_1111)
+    }
+
+    /// Configures the axis/high-low AOI conditions (`INT1_CFG_A` / `INT2_CFG_A`) of the given
+    /// interrupt pin
+    pub fn set_interrupt_config(
+        &mut self,
+        pin: InterruptPin,
+        config: InterruptConfig,
+    ) -> Result<(), E> {
+        self.write_accel_register(pin.cfg_register(), config.to_byte())
+    }
+
+    /// Reads back which AOI condition fired (`INT1_SRC_A` / `INT2_SRC_A`) on the given
+    /// interrupt pin
+    pub fn interrupt_src(&mut self, pin: InterruptPin) -> Result<InterruptSrc, E> {
+        let r = self.read_accel_register(pin.src_register())?;
+
+        Ok(InterruptSrc::from_byte(r))
+    }
+
+    /// Configures the accelerometer FIFO
+    pub fn set_fifo_mode(&mut self, mode: FifoMode) -> Result<(), E> {
+        self.modify_accel_register(accel::Register::CTRL_REG5_A, |r| {
+            if let FifoMode::Bypass = mode {
+                r & !(1 << 6)
+            } else {
+                r | (1 << 6)
+            }
+        })?;
+
+        self.modify_accel_register(accel::Register::FIFO_CTRL_REG_A, |r| {
+            r & !(0b11 << 6) | (mode.value() << 6)
+        })
+    }
+
+    /// Reads the accelerometer FIFO status
+    pub fn fifo_status(&mut self) -> Result<FifoStatus, E> {
+        let r = self.read_accel_register(accel::Register::FIFO_SRC_REG_A)?;
+
+        Ok(FifoStatus {
+            watermark: r & (1 << 7) != 0,
+            overrun: r & (1 << 6) != 0,
+            empty: r & (1 << 5) != 0,
+            level: r & 0b0001_1111,
+        })
+    }
+
+    /// Drains the accelerometer FIFO into `buf`, returning the number of samples read
+    ///
+    /// Reads at most `buf.len()` samples, stopping early if the FIFO empties first. The fill
+    /// level is drained in a single burst read of `OUT_X_L_A` using the auto-increment bit,
+    /// rather than one transaction per sample.
+    pub fn read_accel_fifo(&mut self, buf: &mut [I16x3]) -> Result<usize, E> {
+        // the FIFO is 32 samples deep; FIFO_SRC_REG_A's level field can't report more than that
+        const FIFO_DEPTH: usize = 32;
+
+        let level = usize(self.fifo_status()?.level);
+        let n = cmp::min(cmp::min(level, FIFO_DEPTH), buf.len());
+
+        let mut buffer = [0u8; FIFO_DEPTH * 6];
+        self.read_accel_registers_into(accel::Register::OUT_X_L_A, &mut buffer[..n * 6])?;
+
+        for (sample, raw) in buf[..n].iter_mut().zip(buffer[..n * 6].chunks_exact(6)) {
+            *sample = I16x3 {
+                x: (u16(raw[0]) + (u16(raw[1]) << 8)) as i16,
+                y: (u16(raw[2]) + (u16(raw[3]) << 8)) as i16,
+                z: (u16(raw[4]) + (u16(raw[5]) << 8)) as i16,
+            };
+        }
+
+        Ok(n)
+    }
+
+    /// Configures the click/double-click detection engine
+    ///
+    /// `config` selects which axes and single-vs-double clicks raise an interrupt;
+    /// `threshold` is the 7-bit click acceleration threshold (`CLICK_THS_A`); `time_limit`,
+    /// `time_latency` and `time_window` set the shock, latency and double-click timing windows
+    /// (`TIME_LIMIT_A`, `TIME_LATENCY_A`, `TIME_WINDOW_A`) in units of `1 / ODR`
+    ///
+    /// Route the click interrupt to a pin with `enable_int1`/`enable_int2` using
+    /// `Int1Source::Click`/`Int2Source::Click`
+    pub fn configure_click(
+        &mut self,
+        config: ClickConfig,
+        threshold: u8,
+        time_limit: u8,
+        time_latency: u8,
+        time_window: u8,
+    ) -> Result<(), E> {
+        self.write_accel_register(accel::Register::CLICK_CFG_A, config.to_byte())?;
+        self.write_accel_register(accel::Register::CLICK_THS_A, threshold & 0b0111_1111)?;
+        self.write_accel_register(accel::Register::TIME_LIMIT_A, time_limit & 0b0111_1111)?;
+        self.write_accel_register(accel::Register::TIME_LATENCY_A, time_latency)?;
+        self.write_accel_register(accel::Register::TIME_WINDOW_A, time_window)
+    }
+
+    /// Reads back which click condition fired (`CLICK_SRC_A`)
+    pub fn click_src(&mut self) -> Result<ClickSrc, E> {
+        let r = self.read_accel_register(accel::Register::CLICK_SRC_A)?;
+
+        Ok(ClickSrc::from_byte(r))
+    }
+
     fn modify_accel_register<F>(&mut self, reg: accel::Register, f: F) -> Result<(), E>
     where
         F: FnOnce(u8) -> u8,
@@ -132,18 +401,21 @@ where
         N: ArrayLength<u8>,
     {
         let mut buffer: GenericArray<u8, N> = unsafe { mem::MaybeUninit::uninit().assume_init() };
-
-        {
-            let buffer: &mut [u8] = &mut buffer;
-
-            const MULTI: u8 = 1 << 7;
-            self.i2c
-                .write_read(accel::ADDRESS, &[reg.addr() | MULTI], buffer)?;
-        }
-
+        self.read_accel_registers_into(reg, &mut buffer)?;
         Ok(buffer)
     }
 
+    // burst-reads `buffer.len()` bytes starting at `reg`, using the auto-increment bit
+    fn read_accel_registers_into(
+        &mut self,
+        reg: accel::Register,
+        buffer: &mut [u8],
+    ) -> Result<(), E> {
+        const MULTI: u8 = 1 << 7;
+        self.i2c
+            .write_read(accel::ADDRESS, &[reg.addr() | MULTI], buffer)
+    }
+
     fn read_accel_register(&mut self, reg: accel::Register) -> Result<u8, E> {
         self.read_accel_registers::<U1>(reg).map(|b| b[0])
     }
@@ -189,7 +461,155 @@ pub struct I16x3 {
     pub z: i16,
 }
 
+/// XYZ triple, scaled to a physical unit (e.g. mg, mG)
+#[derive(Debug)]
+pub struct I32x3 {
+    /// X component
+    pub x: i32,
+    /// Y component
+    pub y: i32,
+    /// Z component
+    pub z: i32,
+}
+
+/// Software hard-iron (and soft-iron) magnetometer calibration
+///
+/// The LSM303DLHC has no on-chip offset cancellation, so nearby ferrous/magnetized material
+/// biases every axis. Feed raw samples via `update` while rotating the board through every
+/// orientation, then call `finish` to freeze the per-axis offset (and scale) applied by
+/// [`magnetic_field_calibrated`](struct.Lsm303dlhc.html#method.magnetic_field_calibrated).
+pub struct MagCalibration {
+    min: I16x3,
+    max: I16x3,
+    offset_x: i32,
+    offset_y: i32,
+    offset_z: i32,
+    scale_x: f32,
+    scale_y: f32,
+    scale_z: f32,
+}
+
+impl MagCalibration {
+    /// Starts a new calibration session
+    pub fn new() -> Self {
+        MagCalibration {
+            min: I16x3 {
+                x: i16::MAX,
+                y: i16::MAX,
+                z: i16::MAX,
+            },
+            max: I16x3 {
+                x: i16::MIN,
+                y: i16::MIN,
+                z: i16::MIN,
+            },
+            offset_x: 0,
+            offset_y: 0,
+            offset_z: 0,
+            scale_x: 1.,
+            scale_y: 1.,
+            scale_z: 1.,
+        }
+    }
+
+    /// Feeds a raw magnetometer `sample` (see `Lsm303dlhc::mag`) into the calibration
+    pub fn update(&mut self, sample: &I16x3) {
+        self.min.x = cmp::min(self.min.x, sample.x);
+        self.min.y = cmp::min(self.min.y, sample.y);
+        self.min.z = cmp::min(self.min.z, sample.z);
+
+        self.max.x = cmp::max(self.max.x, sample.x);
+        self.max.y = cmp::max(self.max.y, sample.y);
+        self.max.z = cmp::max(self.max.z, sample.z);
+    }
+
+    /// Freezes the hard-iron offset, and soft-iron scale, computed from the samples seen so far
+    ///
+    /// Does nothing if no sample has been fed via `update` yet. An axis whose span is zero
+    /// (e.g. the board was never rotated through it) keeps a neutral scale of `1.0` instead of
+    /// blowing up to `NaN`/`inf`.
+    pub fn finish(&mut self) {
+        if self.min.x > self.max.x {
+            // no sample has been fed yet; nothing to compute
+            return;
+        }
+
+        self.offset_x = (i32(self.max.x) + i32(self.min.x)) / 2;
+        self.offset_y = (i32(self.max.y) + i32(self.min.y)) / 2;
+        self.offset_z = (i32(self.max.z) + i32(self.min.z)) / 2;
+
+        let span_x = (i32(self.max.x) - i32(self.min.x)) / 2;
+        let span_y = (i32(self.max.y) - i32(self.min.y)) / 2;
+        let span_z = (i32(self.max.z) - i32(self.min.z)) / 2;
+        let average_span = (span_x + span_y + span_z) as f32 / 3.;
+
+        self.scale_x = Self::scale(average_span, span_x);
+        self.scale_y = Self::scale(average_span, span_y);
+        self.scale_z = Self::scale(average_span, span_z);
+    }
+
+    fn scale(average_span: f32, span: i32) -> f32 {
+        if span == 0 {
+            1.
+        } else {
+            average_span / span as f32
+        }
+    }
+
+    fn apply(&self, raw: &I16x3) -> (i32, i32, i32) {
+        (
+            ((i32(raw.x) - self.offset_x) as f32 * self.scale_x) as i32,
+            ((i32(raw.y) - self.offset_y) as f32 * self.scale_y) as i32,
+            ((i32(raw.z) - self.offset_z) as f32 * self.scale_z) as i32,
+        )
+    }
+}
+
+impl Default for MagCalibration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configuration used to fully initialize a `Lsm303dlhc` in a single call
+///
+/// See `Lsm303dlhc::new_with_config`. `Config::default()` matches the hardcoded configuration
+/// previously applied by `Lsm303dlhc::new`.
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Accelerometer output data rate
+    pub accel_odr: AccelOdr,
+    /// Accelerometer sensitivity (full-scale range)
+    pub accel_sensitivity: Sensitivity,
+    /// Magnetometer output data rate
+    pub mag_odr: MagOdr,
+    /// Magnetometer gain (full-scale range)
+    pub mag_gain: MagGain,
+    /// Enable the temperature sensor
+    pub temp_enable: bool,
+    /// Enable the accelerometer high-resolution output mode
+    pub high_resolution: bool,
+    /// Enable the accelerometer block data update (output registers are not updated until both
+    /// the MSB and LSB have been read)
+    pub block_data_update: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            accel_odr: AccelOdr::Hz400,
+            accel_sensitivity: Sensitivity::G1,
+            mag_odr: MagOdr::Hz3,
+            mag_gain: MagGain::G1_3,
+            temp_enable: true,
+            high_resolution: false,
+            block_data_update: false,
+        }
+    }
+}
+
 /// Accelerometer Output Data Rate
+#[derive(Clone, Copy)]
 pub enum AccelOdr {
     /// 1 Hz
     Hz1 = 0b0001,
@@ -208,6 +628,7 @@ pub enum AccelOdr {
 }
 
 /// Magnetometer Output Data Rate
+#[derive(Clone, Copy)]
 pub enum MagOdr {
     /// 0.75 Hz
     Hz0_75 = 0b000,
@@ -227,6 +648,331 @@ pub enum MagOdr {
     Hz220 = 0b111,
 }
 
+/// Accelerometer FIFO mode
+#[derive(Clone, Copy)]
+pub enum FifoMode {
+    /// The FIFO is bypassed; `OUT_X/Y/Z_A` always hold the latest sample
+    Bypass = 0b00,
+    /// The FIFO collects samples until it is full, then stops
+    Fifo = 0b01,
+    /// The FIFO discards the oldest sample to make room for each new one
+    Stream = 0b10,
+    /// Behaves like `Stream` until triggered, then behaves like `Fifo`
+    StreamToFifo = 0b11,
+}
+
+impl FifoMode {
+    fn value(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Accelerometer FIFO status, as read from `FIFO_SRC_REG_A`
+#[derive(Debug)]
+pub struct FifoStatus {
+    /// The FIFO has reached the configured watermark level
+    pub watermark: bool,
+    /// The FIFO has overrun; samples have been lost
+    pub overrun: bool,
+    /// The FIFO is empty
+    pub empty: bool,
+    /// Number of unread samples currently stored in the FIFO
+    pub level: u8,
+}
+
+/// Accelerometer status, as read from `STATUS_REG_A`
+#[derive(Debug)]
+pub struct AccelStatus {
+    /// New data is available on the X axis
+    pub x_new_data: bool,
+    /// New data is available on the Y axis
+    pub y_new_data: bool,
+    /// New data is available on the Z axis
+    pub z_new_data: bool,
+    /// New data is available on all three axes
+    pub zyx_new_data: bool,
+    /// Data on the X axis was overwritten before it was read
+    pub x_overrun: bool,
+    /// Data on the Y axis was overwritten before it was read
+    pub y_overrun: bool,
+    /// Data on the Z axis was overwritten before it was read
+    pub z_overrun: bool,
+    /// Data on at least one of the three axes was overwritten before it was read
+    pub zyx_overrun: bool,
+}
+
+/// Magnetometer status, as read from `SR_REG_M`
+#[derive(Debug)]
+pub struct MagStatus {
+    /// New data is available on all three axes
+    pub data_ready: bool,
+    /// Data has been locked because `OUT_X/Y/Z_H/L_M` is being read
+    pub lock: bool,
+}
+
+/// An interrupt pin of the accelerometer
+#[derive(Clone, Copy)]
+pub enum InterruptPin {
+    /// INT1
+    Int1,
+    /// INT2
+    Int2,
+}
+
+impl InterruptPin {
+    fn ths_register(&self) -> accel::Register {
+        match *self {
+            InterruptPin::Int1 => accel::Register::INT1_THS_A,
+            InterruptPin::Int2 => accel::Register::INT2_THS_A,
+        }
+    }
+
+    fn duration_register(&self) -> accel::Register {
+        match *self {
+            InterruptPin::Int1 => accel::Register::INT1_DURATION_A,
+            InterruptPin::Int2 => accel::Register::INT2_DURATION_A,
+        }
+    }
+
+    fn cfg_register(&self) -> accel::Register {
+        match *self {
+            InterruptPin::Int1 => accel::Register::INT1_CFG_A,
+            InterruptPin::Int2 => accel::Register::INT2_CFG_A,
+        }
+    }
+
+    fn src_register(&self) -> accel::Register {
+        match *self {
+            InterruptPin::Int1 => accel::Register::INT1_SRC_A,
+            InterruptPin::Int2 => accel::Register::INT2_SRC_A,
+        }
+    }
+}
+
+/// Interrupt sources that can be routed to the INT1 pin (`CTRL_REG3_A`)
+#[derive(Clone, Copy)]
+pub enum Int1Source {
+    /// Accelerometer data-ready
+    DataReady1,
+    /// Magnetometer/temperature data-ready
+    DataReady2,
+    /// FIFO watermark
+    FifoWatermark,
+    /// FIFO overrun
+    FifoOverrun,
+    /// AOI1 (`INT1_CFG_A`/`INT1_SRC_A`) interrupt
+    Aoi1,
+    /// AOI2 (`INT2_CFG_A`/`INT2_SRC_A`) interrupt
+    Aoi2,
+    /// Click interrupt
+    Click,
+}
+
+impl Int1Source {
+    fn mask(&self) -> u8 {
+        match *self {
+            Int1Source::Click => 1 << 7,
+            Int1Source::Aoi1 => 1 << 6,
+            Int1Source::Aoi2 => 1 << 5,
+            Int1Source::DataReady1 => 1 << 4,
+            Int1Source::DataReady2 => 1 << 3,
+            Int1Source::FifoWatermark => 1 << 2,
+            Int1Source::FifoOverrun => 1 << 1,
+        }
+    }
+}
+
+/// Interrupt sources that can be routed to the INT2 pin (`CTRL_REG6_A`)
+#[derive(Clone, Copy)]
+pub enum Int2Source {
+    /// Click interrupt
+    Click,
+    /// AOI1 (`INT1_CFG_A`/`INT1_SRC_A`) interrupt
+    Aoi1,
+    /// AOI2 (`INT2_CFG_A`/`INT2_SRC_A`) interrupt
+    Aoi2,
+    /// Boot status
+    Boot,
+}
+
+impl Int2Source {
+    fn mask(&self) -> u8 {
+        match *self {
+            Int2Source::Click => 1 << 7,
+            Int2Source::Aoi1 => 1 << 6,
+            Int2Source::Aoi2 => 1 << 5,
+            Int2Source::Boot => 1 << 4,
+        }
+    }
+}
+
+/// AOI (And/Or Interrupt) condition configuration for `INT1_CFG_A` / `INT2_CFG_A`
+///
+/// Each `_high`/`_low` flag enables the corresponding axis/direction as an OR (or AND, see
+/// `and`) condition of the interrupt generator
+#[derive(Clone, Copy, Default)]
+pub struct InterruptConfig {
+    /// Combine the enabled conditions with AND instead of OR
+    pub and: bool,
+    /// Enable the 6-direction position recognition function instead of AOI
+    pub six_direction: bool,
+    /// Interrupt on Z high
+    pub z_high: bool,
+    /// Interrupt on Z low
+    pub z_low: bool,
+    /// Interrupt on Y high
+    pub y_high: bool,
+    /// Interrupt on Y low
+    pub y_low: bool,
+    /// Interrupt on X high
+    pub x_high: bool,
+    /// Interrupt on X low
+    pub x_low: bool,
+}
+
+impl InterruptConfig {
+    fn to_byte(self) -> u8 {
+        let mut r = 0;
+
+        if self.and {
+            r |= 1 << 7;
+        }
+        if self.six_direction {
+            r |= 1 << 6;
+        }
+        if self.z_high {
+            r |= 1 << 5;
+        }
+        if self.z_low {
+            r |= 1 << 4;
+        }
+        if self.y_high {
+            r |= 1 << 3;
+        }
+        if self.y_low {
+            r |= 1 << 2;
+        }
+        if self.x_high {
+            r |= 1 << 1;
+        }
+        if self.x_low {
+            r |= 1 << 0;
+        }
+
+        r
+    }
+}
+
+/// Decoded AOI interrupt source, as read from `INT1_SRC_A` / `INT2_SRC_A`
+#[derive(Debug)]
+pub struct InterruptSrc {
+    /// At least one of the enabled interrupt conditions was met
+    pub interrupt_active: bool,
+    /// Z high condition was met
+    pub z_high: bool,
+    /// Z low condition was met
+    pub z_low: bool,
+    /// Y high condition was met
+    pub y_high: bool,
+    /// Y low condition was met
+    pub y_low: bool,
+    /// X high condition was met
+    pub x_high: bool,
+    /// X low condition was met
+    pub x_low: bool,
+}
+
+impl InterruptSrc {
+    fn from_byte(r: u8) -> Self {
+        InterruptSrc {
+            interrupt_active: r & (1 << 6) != 0,
+            z_high: r & (1 << 5) != 0,
+            z_low: r & (1 << 4) != 0,
+            y_high: r & (1 << 3) != 0,
+            y_low: r & (1 << 2) != 0,
+            x_high: r & (1 << 1) != 0,
+            x_low: r & (1 << 0) != 0,
+        }
+    }
+}
+
+/// Click/double-click detection configuration for `CLICK_CFG_A`
+#[derive(Clone, Copy, Default)]
+pub struct ClickConfig {
+    /// Enable double-click detection on the Z axis
+    pub z_double: bool,
+    /// Enable single-click detection on the Z axis
+    pub z_single: bool,
+    /// Enable double-click detection on the Y axis
+    pub y_double: bool,
+    /// Enable single-click detection on the Y axis
+    pub y_single: bool,
+    /// Enable double-click detection on the X axis
+    pub x_double: bool,
+    /// Enable single-click detection on the X axis
+    pub x_single: bool,
+}
+
+impl ClickConfig {
+    fn to_byte(self) -> u8 {
+        let mut r = 0;
+
+        if self.z_double {
+            r |= 1 << 5;
+        }
+        if self.z_single {
+            r |= 1 << 4;
+        }
+        if self.y_double {
+            r |= 1 << 3;
+        }
+        if self.y_single {
+            r |= 1 << 2;
+        }
+        if self.x_double {
+            r |= 1 << 1;
+        }
+        if self.x_single {
+            r |= 1 << 0;
+        }
+
+        r
+    }
+}
+
+/// Decoded click source, as read from `CLICK_SRC_A`
+#[derive(Debug)]
+pub struct ClickSrc {
+    /// At least one of the enabled click conditions was met
+    pub interrupt_active: bool,
+    /// A double-click was detected
+    pub double_click: bool,
+    /// A single-click was detected
+    pub single_click: bool,
+    /// The click had a negative sign
+    pub sign_negative: bool,
+    /// The click was on the Z axis
+    pub z: bool,
+    /// The click was on the Y axis
+    pub y: bool,
+    /// The click was on the X axis
+    pub x: bool,
+}
+
+impl ClickSrc {
+    fn from_byte(r: u8) -> Self {
+        ClickSrc {
+            interrupt_active: r & (1 << 6) != 0,
+            double_click: r & (1 << 5) != 0,
+            single_click: r & (1 << 4) != 0,
+            sign_negative: r & (1 << 3) != 0,
+            z: r & (1 << 2) != 0,
+            y: r & (1 << 1) != 0,
+            x: r & (1 << 0) != 0,
+        }
+    }
+}
+
 /// Acceleration sensitivity
 #[derive(Clone, Copy)]
 pub enum Sensitivity {
@@ -244,4 +990,65 @@ impl Sensitivity {
     fn value(&self) -> u8 {
         *self as u8
     }
+
+    // mg / LSB, after the 4-bit left-justified shift
+    fn mg_per_lsb(&self) -> u16 {
+        match *self {
+            Sensitivity::G1 => 1,
+            Sensitivity::G2 => 2,
+            Sensitivity::G4 => 4,
+            Sensitivity::G12 => 12,
+        }
+    }
+}
+
+/// Magnetometer gain (full-scale range)
+#[derive(Clone, Copy)]
+pub enum MagGain {
+    /// Range: [-1.3, +1.3] gauss
+    G1_3 = 0b001,
+    /// Range: [-1.9, +1.9] gauss
+    G1_9 = 0b010,
+    /// Range: [-2.5, +2.5] gauss
+    G2_5 = 0b011,
+    /// Range: [-4.0, +4.0] gauss
+    G4_0 = 0b100,
+    /// Range: [-4.7, +4.7] gauss
+    G4_7 = 0b101,
+    /// Range: [-5.6, +5.6] gauss
+    G5_6 = 0b110,
+    /// Range: [-8.1, +8.1] gauss
+    G8_1 = 0b111,
+}
+
+impl MagGain {
+    fn value(&self) -> u8 {
+        *self as u8
+    }
+
+    // LSB / gauss, XY axes
+    fn xy(&self) -> u16 {
+        match *self {
+            MagGain::G1_3 => 1100,
+            MagGain::G1_9 => 855,
+            MagGain::G2_5 => 670,
+            MagGain::G4_0 => 450,
+            MagGain::G4_7 => 400,
+            MagGain::G5_6 => 330,
+            MagGain::G8_1 => 230,
+        }
+    }
+
+    // LSB / gauss, Z axis
+    fn z(&self) -> u16 {
+        match *self {
+            MagGain::G1_3 => 980,
+            MagGain::G1_9 => 760,
+            MagGain::G2_5 => 600,
+            MagGain::G4_0 => 400,
+            MagGain::G4_7 => 355,
+            MagGain::G5_6 => 295,
+            MagGain::G8_1 => 205,
+        }
+    }
 }